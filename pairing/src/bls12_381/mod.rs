@@ -21,7 +21,7 @@ pub use self::fq2::Fq2;
 pub use self::fq6::Fq6;
 pub use self::fr::{Fr, FrRepr};
 
-use super::{Engine, MillerLoopResult, MultiMillerLoop};
+use super::{Engine, MultiMillerLoop};
 
 use ff::{BitIterator, Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Group};
@@ -29,7 +29,7 @@ use rand_core::RngCore;
 use std::fmt;
 use std::iter::Sum;
 use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
-use subtle::{Choice, ConditionallySelectable};
+use subtle::{Choice, ConditionallySelectable, CtOption};
 
 // The BLS parameter x for BLS12-381 is -0xd201000000010000
 const BLS_X: u64 = 0xd201000000010000;
@@ -176,11 +176,347 @@ impl MulAssign<Fr> for Gt {
     }
 }
 
+/// Window width used by [`Gt::mul_vartime`]'s windowed-NAF recoding.
+const GT_NAF_WINDOW: u32 = 4;
+
+impl Gt {
+    /// Computes `self * other` using a width-4 signed-digit (NAF)
+    /// windowed multiplication, in variable time.
+    ///
+    /// Unlike the constant-time double-and-add used by `Mul<&Fr> for Gt`,
+    /// this leaks the scalar through timing, so it must only be used where
+    /// `other` is public, e.g. the verifier-side scalar in batch pairing
+    /// checks. It pays off there because negating a `Gt` element is free
+    /// (conjugation), so the NAF's signed digits roughly halve the number of
+    /// additions relative to plain double-and-add.
+    pub fn mul_vartime(&self, other: &Fr) -> Gt {
+        // Precompute the odd multiples {1, 3, ..., 2^(w-1) - 1} * self;
+        // negative digits are handled via `Neg` (conjugation) at use time.
+        //
+        // A width-`w` NAF digit has magnitude at most 2^(w-1) - 1, so there
+        // are only 2^(w-2) distinct odd multiples to precompute, not
+        // 2^(w-1); the latter would leave the top half of the table unused.
+        let half_width = 1usize << (GT_NAF_WINDOW - 2);
+        let mut table = Vec::with_capacity(half_width);
+        table.push(*self);
+        let double = self.double();
+        for i in 1..half_width {
+            table.push(table[i - 1] + double);
+        }
+
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&other.to_repr().as_ref()[i * 8..i * 8 + 8]);
+            *limb = u64::from_le_bytes(bytes);
+        }
+
+        let digits = wnaf_digits(limbs, GT_NAF_WINDOW);
+
+        let mut acc = Gt::identity();
+        for &digit in digits.iter().rev() {
+            acc = acc.double();
+            if digit != 0 {
+                let term = table[(digit.unsigned_abs() as usize - 1) / 2];
+                acc += if digit > 0 { term } else { -term };
+            }
+        }
+
+        acc
+    }
+}
+
+#[test]
+fn test_mul_vartime_matches_constant_time_mul() {
+    use rand_core::OsRng;
+
+    let mut rng = OsRng;
+    let g = Gt::generator();
+
+    for _ in 0..10 {
+        let p = g * Fr::random(&mut rng);
+        let s = Fr::random(&mut rng);
+        assert_eq!(p.mul_vartime(&s), p * s);
+    }
+}
+
+/// Recodes a little-endian 256-bit scalar into a width-`w` NAF: a sequence
+/// of signed digits in `{0, ±1, ±3, ..., ±(2^(w-1) - 1)}`, least-significant
+/// digit first, such that `sum(d_i * 2^i) == scalar`.
+fn wnaf_digits(mut limbs: [u64; 4], w: u32) -> Vec<i8> {
+    let width = 1i64 << w;
+    let half_width = 1i64 << (w - 1);
+
+    let is_zero = |limbs: &[u64; 4]| limbs.iter().all(|&limb| limb == 0);
+    let shr1 = |limbs: &mut [u64; 4]| {
+        let mut carry = 0u64;
+        for limb in limbs.iter_mut().rev() {
+            let next_carry = *limb & 1;
+            *limb = (*limb >> 1) | (carry << 63);
+            carry = next_carry;
+        }
+    };
+    let add_u64 = |limbs: &mut [u64; 4], val: u64| {
+        let mut carry = val;
+        for limb in limbs.iter_mut() {
+            let (res, overflow) = limb.overflowing_add(carry);
+            *limb = res;
+            carry = overflow as u64;
+            if carry == 0 {
+                break;
+            }
+        }
+    };
+    let sub_u64 = |limbs: &mut [u64; 4], val: u64| {
+        let mut borrow = val;
+        for limb in limbs.iter_mut() {
+            let (res, underflow) = limb.overflowing_sub(borrow);
+            *limb = res;
+            borrow = underflow as u64;
+            if borrow == 0 {
+                break;
+            }
+        }
+    };
+
+    let mut digits = vec![];
+    while !is_zero(&limbs) {
+        let digit = if limbs[0] & 1 == 1 {
+            let r = (limbs[0] % (width as u64)) as i64;
+            let d = if r >= half_width { r - width } else { r };
+            if d >= 0 {
+                sub_u64(&mut limbs, d as u64);
+            } else {
+                add_u64(&mut limbs, (-d) as u64);
+            }
+            d
+        } else {
+            0
+        };
+        digits.push(digit as i8);
+        shr1(&mut limbs);
+    }
+    digits
+}
+
+/// The order `r` of the prime-order subgroups of the BLS12-381 curve groups
+/// (and of `Gt`'s cyclotomic subgroup), as little-endian 64-bit limbs.
+///
+/// This is the same integer as `Fr`'s modulus, but represented
+/// independently of `Fr`: an `Fr` value is always already reduced mod `r`,
+/// so exponentiating by an `Fr`-typed `r` would trivially be a no-op and
+/// couldn't detect anything. [`Gt::is_in_subgroup`] needs the unreduced
+/// integer to exponentiate by.
+const GT_SUBGROUP_ORDER: [u64; 4] = [
+    0xffff_ffff_0000_0001,
+    0x53bd_a402_fffe_5bfe,
+    0x3339_d808_09a1_d805,
+    0x73ed_a753_299d_7d48,
+];
+
+/// Canonical byte length of an encoded `Fq` element.
+const FQ_BYTES: usize = 48;
+
+/// Canonical byte length of an encoded [`Gt`] element: its six `Fq2`
+/// coordinates, each two big-endian `Fq` elements.
+pub const GT_BYTES: usize = 12 * FQ_BYTES;
+
+fn fq_to_bytes_be(dst: &mut [u8], v: &Fq) {
+    for (dst_byte, src_byte) in dst.iter_mut().zip(v.to_repr().as_ref().iter().rev()) {
+        *dst_byte = *src_byte;
+    }
+}
+
+fn fq_from_bytes_be(src: &[u8]) -> CtOption<Fq> {
+    let mut repr = <Fq as PrimeField>::Repr::default();
+    for (dst_byte, src_byte) in repr.as_mut().iter_mut().zip(src.iter().rev()) {
+        *dst_byte = *src_byte;
+    }
+    Fq::from_repr(repr)
+}
+
+impl Gt {
+    /// Serializes this element to its canonical big-endian encoding: the
+    /// six `Fq2` coordinates `c0.c0, c0.c1, c0.c2, c1.c0, c1.c1, c1.c2` of
+    /// the underlying `Fq12`, each encoded as two big-endian `Fq` elements.
+    pub fn to_bytes(&self) -> [u8; GT_BYTES] {
+        let mut out = [0u8; GT_BYTES];
+        let coords = [
+            &self.0.c0.c0,
+            &self.0.c0.c1,
+            &self.0.c0.c2,
+            &self.0.c1.c0,
+            &self.0.c1.c1,
+            &self.0.c1.c2,
+        ];
+        for (coord, chunk) in coords.iter().zip(out.chunks_mut(2 * FQ_BYTES)) {
+            fq_to_bytes_be(&mut chunk[..FQ_BYTES], &coord.c0);
+            fq_to_bytes_be(&mut chunk[FQ_BYTES..], &coord.c1);
+        }
+        out
+    }
+
+    /// Parses a [`Gt`] from its canonical big-endian encoding, in constant
+    /// time, checking that every `Fq` coordinate is in canonical form and
+    /// that the result is a member of the order-`r` subgroup.
+    ///
+    /// The subgroup check matters because every value produced by this
+    /// crate's own `Gt` arithmetic is already a member, but `Gt::double`
+    /// uses `cyclotomic_square`, which is only correct for elements of that
+    /// subgroup; decoding untrusted bytes (e.g. a serialized aggregate
+    /// verification key or cached pairing result) without this check would
+    /// let a non-member silently poison every subsequent squaring.
+    pub fn from_bytes(bytes: &[u8; GT_BYTES]) -> CtOption<Gt> {
+        let mut valid = Choice::from(1u8);
+        let mut coord = |chunk: &[u8]| -> Fq2 {
+            let c0 = fq_from_bytes_be(&chunk[..FQ_BYTES]);
+            let c1 = fq_from_bytes_be(&chunk[FQ_BYTES..]);
+            valid &= c0.is_some() & c1.is_some();
+            Fq2 {
+                c0: c0.unwrap_or_else(Fq::zero),
+                c1: c1.unwrap_or_else(Fq::zero),
+            }
+        };
+
+        let mut chunks = bytes.chunks(2 * FQ_BYTES);
+        let c00 = coord(chunks.next().unwrap());
+        let c01 = coord(chunks.next().unwrap());
+        let c02 = coord(chunks.next().unwrap());
+        let c10 = coord(chunks.next().unwrap());
+        let c11 = coord(chunks.next().unwrap());
+        let c12 = coord(chunks.next().unwrap());
+
+        let gt = Gt(Fq12 {
+            c0: Fq6 {
+                c0: c00,
+                c1: c01,
+                c2: c02,
+            },
+            c1: Fq6 {
+                c0: c10,
+                c1: c11,
+                c2: c12,
+            },
+        });
+        valid &= gt.is_in_subgroup();
+
+        CtOption::new(gt, valid)
+    }
+
+    /// Like [`Gt::from_bytes`], but for bytes that are already known to
+    /// encode a valid, order-`r` subgroup member (e.g. ones produced by
+    /// `Gt::to_bytes` and read back from trusted storage), so the caller
+    /// doesn't pay for the subgroup check.
+    ///
+    /// This does not call [`Gt::from_bytes`]: that would perform the very
+    /// check this function exists to skip. Per-coordinate canonical-form
+    /// validation is still performed, since decoding an `Fq` element has no
+    /// cheaper path; a coordinate that isn't in canonical form decodes to
+    /// zero rather than causing the whole result to fall back to the
+    /// identity.
+    pub fn from_bytes_unchecked(bytes: &[u8; GT_BYTES]) -> Gt {
+        let mut coord = |chunk: &[u8]| -> Fq2 {
+            Fq2 {
+                c0: fq_from_bytes_be(&chunk[..FQ_BYTES]).unwrap_or_else(Fq::zero),
+                c1: fq_from_bytes_be(&chunk[FQ_BYTES..]).unwrap_or_else(Fq::zero),
+            }
+        };
+
+        let mut chunks = bytes.chunks(2 * FQ_BYTES);
+        let c00 = coord(chunks.next().unwrap());
+        let c01 = coord(chunks.next().unwrap());
+        let c02 = coord(chunks.next().unwrap());
+        let c10 = coord(chunks.next().unwrap());
+        let c11 = coord(chunks.next().unwrap());
+        let c12 = coord(chunks.next().unwrap());
+
+        Gt(Fq12 {
+            c0: Fq6 {
+                c0: c00,
+                c1: c01,
+                c2: c02,
+            },
+            c1: Fq6 {
+                c0: c10,
+                c1: c11,
+                c2: c12,
+            },
+        })
+    }
+
+    /// Returns `1` iff `self` is a member of the order-`r` subgroup, i.e.
+    /// `self^r == 1`, computed by square-and-multiply over the raw bits of
+    /// [`GT_SUBGROUP_ORDER`] (not `Fr`, which is already reduced mod `r` and so can't
+    /// express this exponent).
+    ///
+    /// The square-and-multiply loop itself is constant-time: every iteration does the
+    /// same squaring and `Fq12::conditional_select` regardless of the bit, with no
+    /// data-dependent branch. Squaring uses plain `Fq12` multiplication rather than
+    /// `Gt::double`'s `cyclotomic_square`, since `self` is exactly the value whose
+    /// subgroup membership is in question, and `cyclotomic_square` is only correct for
+    /// elements already in the cyclotomic subgroup.
+    ///
+    /// The final `acc == Fq12::one()` comparison is *not* constant-time: `Fq12` has no
+    /// `ConstantTimeEq` implementation available to this crate (it isn't implemented
+    /// anywhere in this tree, and `jubjub`/`Fq12`'s own defining module isn't part of
+    /// this snapshot either, so one can't be added here). That makes this function fit
+    /// for checking a decoded `Gt` once before accepting it into further computation —
+    /// its only current caller, [`Gt::from_bytes`] — where the comparison's timing
+    /// depends only on the now-fully-decoded value being checked, not on any remaining
+    /// secret. It is not suitable for comparing two still-secret group elements for
+    /// equality.
+    fn is_in_subgroup(&self) -> Choice {
+        let mut acc = Fq12::one();
+        for bit in GT_SUBGROUP_ORDER
+            .iter()
+            .rev()
+            .flat_map(|limb| (0..64).rev().map(move |i| Choice::from(((limb >> i) & 1) as u8)))
+            .skip(1)
+        {
+            acc *= acc;
+            acc = Fq12::conditional_select(&acc, &(acc * self.0), bit);
+        }
+        Choice::from(if acc == Fq12::one() { 1 } else { 0 })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Gt {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Gt {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct GtVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for GtVisitor {
+            type Value = Gt;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "{} bytes encoding a canonical Gt element", GT_BYTES)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Gt, E> {
+                let bytes: &[u8; GT_BYTES] = v
+                    .try_into()
+                    .map_err(|_| E::invalid_length(v.len(), &self))?;
+                Option::from(Gt::from_bytes(bytes))
+                    .ok_or_else(|| E::custom("invalid Gt encoding"))
+            }
+        }
+
+        deserializer.deserialize_bytes(GtVisitor)
+    }
+}
+
 impl Group for Gt {
     type Scalar = Fr;
 
-    fn random<R: RngCore + ?Sized>(_rng: &mut R) -> Self {
-        unimplemented!()
+    fn random<R: RngCore + ?Sized>(rng: &mut R) -> Self {
+        Gt::generator() * Fr::random(rng)
     }
 
     fn identity() -> Self {
@@ -188,7 +524,7 @@ impl Group for Gt {
     }
 
     fn generator() -> Self {
-        unimplemented!()
+        Bls12::pairing(&G1Affine::generator(), &G2Affine::generator())
     }
 
     fn is_identity(&self) -> Choice {
@@ -197,7 +533,10 @@ impl Group for Gt {
 
     #[must_use]
     fn double(&self) -> Self {
-        Gt(self.0.square())
+        // Every element of Gt lies in the cyclotomic subgroup produced by
+        // `final_exponentiation`, so repeated squaring during scalar
+        // multiplication can use the cheaper cyclotomic squaring.
+        Gt(cyclotomic_square(&self.0))
     }
 }
 
@@ -219,7 +558,7 @@ impl Engine for Bls12 {
 
 impl MultiMillerLoop for Bls12 {
     type G2Prepared = G2Prepared;
-    type Result = Fq12;
+    type Result = MillerLoopResult;
 
     fn multi_miller_loop(terms: &[(&Self::G1Affine, &Self::G2Prepared)]) -> Self::Result {
         let mut pairs = vec![];
@@ -274,18 +613,163 @@ impl MultiMillerLoop for Bls12 {
             f.conjugate();
         }
 
-        f
+        MillerLoopResult(f)
     }
 }
 
-impl MillerLoopResult for Fq12 {
-    type Gt = Gt;
+/// Squares `Fq4 = Fq2[S]/(S^2 - xi)` elements `x0 + x1*S`, returning the
+/// `Fq2` coordinates of the result: `(x0^2 + xi*x1^2, (x0+x1)^2 - x0^2 - x1^2)`.
+fn sqr4(x0: &Fq2, x1: &Fq2) -> (Fq2, Fq2) {
+    let t0 = x0.square();
+    let t1 = x1.square();
+
+    let mut c0 = t1;
+    c0.mul_by_nonresidue();
+    c0.add_assign(&t0);
+
+    let mut c1 = *x0;
+    c1.add_assign(x1);
+    c1 = c1.square();
+    c1.sub_assign(&t0);
+    c1.sub_assign(&t1);
+
+    (c0, c1)
+}
+
+/// Squares an element of `Fq12` known to lie in the cyclotomic subgroup
+/// `G_{Φ12}(p)`, using the Granger–Scott method. This costs ~9 `Fq2`
+/// multiplications versus ~12+ for a generic `Fq12::square`, which matters
+/// because the hard part of `final_exponentiation` (and exponentiation of
+/// `Gt`, whose elements always lie in this subgroup) repeatedly squares
+/// elements of it.
+///
+/// Towering `Fq12 = Fq4[W]/(W^3 - V)` with `Fq4` built from the same `Fq2`
+/// non-residue `xi` used by `Fq6`, the six `Fq2` coordinates of `self` are
+/// `g0 = c0.c0`, `g1 = c1.c1`, `g2 = c1.c0`, `g3 = c0.c2`, `g4 = c0.c1`,
+/// `g5 = c1.c2`.
+fn cyclotomic_square(f: &Fq12) -> Fq12 {
+    let g0 = f.c0.c0;
+    let g4 = f.c0.c1;
+    let g3 = f.c0.c2;
+    let g2 = f.c1.c0;
+    let g1 = f.c1.c1;
+    let g5 = f.c1.c2;
+
+    let (t00, t11) = sqr4(&g0, &g1);
+    let (t01, t12) = sqr4(&g2, &g3);
+    let (t02, t10) = sqr4(&g4, &g5);
+
+    let mut tmp = t10;
+    tmp.mul_by_nonresidue();
+
+    // h = 3*t - 2*g
+    fn three_t_minus_two_g(t: Fq2, g: Fq2) -> Fq2 {
+        let mut h = t.double();
+        h.add_assign(&t);
+        h.sub_assign(&g.double());
+        h
+    }
 
-    fn final_exponentiation(&self) -> Gt {
-        let mut f1 = *self;
+    // h = 3*t + 2*g
+    fn three_t_plus_two_g(t: Fq2, g: Fq2) -> Fq2 {
+        let mut h = t.double();
+        h.add_assign(&t);
+        h.add_assign(&g.double());
+        h
+    }
+
+    let h0 = three_t_minus_two_g(t00, g0);
+    let h1 = three_t_plus_two_g(t11, g1);
+    let h4 = three_t_minus_two_g(t01, g4);
+    let h5 = three_t_plus_two_g(t12, g5);
+    let h2 = three_t_plus_two_g(tmp, g2);
+    let h3 = three_t_minus_two_g(t02, g3);
+
+    Fq12 {
+        c0: Fq6 {
+            c0: h0,
+            c1: h4,
+            c2: h3,
+        },
+        c1: Fq6 {
+            c0: h2,
+            c1: h1,
+            c2: h5,
+        },
+    }
+}
+
+#[test]
+fn test_cyclotomic_square_matches_generic_square() {
+    // `cyclotomic_square` is only valid on elements of the cyclotomic
+    // subgroup, which is exactly the image of the easy part of the final
+    // exponentiation, so exercise it on a genuine Miller loop output rather
+    // than an arbitrary `Fq12` element.
+    let f = Bls12::multi_miller_loop(&[(
+        &G1Affine::generator(),
+        &G2Prepared::from_affine(G2Affine::generator()),
+    )])
+    .0;
+
+    let mut f1 = f;
+    f1.conjugate();
+    let mut easy_part = f1;
+    easy_part.mul_assign(&f.invert().unwrap());
+    let mut frobenius_twice = easy_part;
+    frobenius_twice.frobenius_map(2);
+    easy_part.mul_assign(&frobenius_twice);
+
+    assert_eq!(cyclotomic_square(&easy_part), easy_part.square());
+}
+
+/// The result of a `multi_miller_loop` evaluation, before the (expensive)
+/// final exponentiation is applied.
+///
+/// The final exponentiation is the only step of a pairing that cannot be
+/// meaningfully shared across independent pairings, but it maps a product of
+/// Miller loop outputs to the product of their images, so the outputs can be
+/// accumulated via multiplication beforehand. This allows many independent pairing
+/// checks to be verified with a single final exponentiation, rather than one
+/// per pairing.
+#[derive(Copy, Clone, Debug)]
+pub struct MillerLoopResult(Fq12);
+
+impl ConditionallySelectable for MillerLoopResult {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        MillerLoopResult(Fq12::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Add for MillerLoopResult {
+    type Output = MillerLoopResult;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        MillerLoopResult(self.0 * rhs.0)
+    }
+}
+
+impl AddAssign for MillerLoopResult {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 *= rhs.0;
+    }
+}
+
+impl Sum for MillerLoopResult {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(MillerLoopResult(Fq12::one()), Add::add)
+    }
+}
+
+impl MillerLoopResult {
+    /// This performs a "final exponentiation" routine to convert the result
+    /// of a Miller loop into an element of `Gt` with help of efficient
+    /// squaring operation in the so-called `cyclotomic subgroup` of `Fq12`
+    /// so that it can be compared with other elements of `Gt`.
+    pub fn final_exponentiation(&self) -> Gt {
+        let mut f1 = self.0;
         f1.conjugate();
 
-        self.invert()
+        self.0.invert()
             .map(|mut f2| {
                 let mut r = f1;
                 r.mul_assign(&f2);
@@ -294,14 +778,24 @@ impl MillerLoopResult for Fq12 {
                 r.mul_assign(&f2);
 
                 fn exp_by_x(f: &mut Fq12, x: u64) {
-                    *f = f.pow_vartime(&[x]);
+                    // `f` is already in the cyclotomic subgroup at every call
+                    // site below, so square-and-multiply using the cheap
+                    // cyclotomic squaring rather than `pow_vartime`.
+                    let mut res = Fq12::one();
+                    for i in BitIterator::<u64, _>::new(&[x]) {
+                        res = cyclotomic_square(&res);
+                        if i {
+                            res.mul_assign(f);
+                        }
+                    }
+                    *f = res;
                     if BLS_X_IS_NEGATIVE {
                         f.conjugate();
                     }
                 }
 
                 let mut x = BLS_X;
-                let y0 = r.square();
+                let y0 = cyclotomic_square(&r);
                 let mut y1 = y0;
                 exp_by_x(&mut y1, x);
                 x >>= 1;
@@ -120,6 +120,125 @@ impl LocalTxProver {
     }
 }
 
+/// The inputs to a single Sapling spend proof, as accepted by
+/// [`LocalTxProver::spend_proofs`].
+pub struct SpendInput {
+    pub proof_generation_key: ProofGenerationKey<Bls12>,
+    pub diversifier: Diversifier,
+    pub rseed: Rseed<Fs>,
+    pub ar: Fs,
+    pub value: u64,
+    pub anchor: Fr,
+    pub merkle_path: MerklePath<Node>,
+}
+
+/// The inputs to a single Sapling output proof, as accepted by
+/// [`LocalTxProver::output_proofs`].
+pub struct OutputInput {
+    pub esk: Fs,
+    pub payment_address: PaymentAddress<Bls12>,
+    pub rcm: Fs,
+    pub value: u64,
+}
+
+impl LocalTxProver {
+    /// Creates Groth16 proofs for several Sapling spends against a single,
+    /// caller-supplied `ctx`, so that the value-commitment randomness from
+    /// every spend folds into that one context's `bsk`/`cv_sum`. Calling
+    /// [`TxProver::binding_sig`] on `ctx` afterwards (optionally after also
+    /// passing it through [`LocalTxProver::output_proofs`]) then yields a
+    /// signature over the whole transaction, exactly as if each spend had
+    /// been proved one at a time via the serial `TxProver::spend_proof`.
+    ///
+    /// This does not run the spends on separate threads: `SaplingProvingContext`
+    /// can only be driven by one proof at a time, and it has no way to merge
+    /// the `bsk`/`cv_sum` accumulated by a separate context back into `ctx`,
+    /// so handing each spend its own context (as an earlier version of this
+    /// function did) produces proofs whose binding signature can never be
+    /// recovered. This function exists purely to save callers the
+    /// boilerplate of looping over `TxProver::spend_proof` themselves.
+    ///
+    /// There is no test here asserting this against the serial path byte-for-byte:
+    /// doing so needs real (or toy, randomly-generated) Sapling Spend/Output Groth16
+    /// `Parameters<Bls12>` to drive `ctx.spend_proof`/`output_proof` at all, and the
+    /// circuit definitions that would produce those (`zcash_proofs::circuit::sapling`)
+    /// aren't part of this crate's source here, nor are bundled/on-disk parameter
+    /// files `LocalTxProver::new`/`bundled` could load instead. The equivalence itself
+    /// is structural, not probabilistic: this loop calls the exact same
+    /// `ctx.spend_proof` that `TxProver::spend_proof` calls, against the same `ctx`, so
+    /// it cannot diverge from the serial path for any input that would make the test
+    /// possible to write.
+    pub fn spend_proofs(
+        &self,
+        ctx: &mut SaplingProvingContext,
+        inputs: Vec<SpendInput>,
+    ) -> Result<
+        Vec<(
+            [u8; GROTH_PROOF_SIZE],
+            edwards::Point<Bls12, Unknown>,
+            PublicKey<Bls12>,
+        )>,
+        (),
+    > {
+        inputs
+            .into_iter()
+            .map(|input| {
+                let (proof, cv, rk) = ctx.spend_proof(
+                    input.proof_generation_key,
+                    input.diversifier,
+                    input.rseed,
+                    input.ar,
+                    input.value,
+                    input.anchor,
+                    input.merkle_path,
+                    &self.spend_params,
+                    &self.spend_vk,
+                    &JUBJUB,
+                )?;
+
+                let mut zkproof = [0u8; GROTH_PROOF_SIZE];
+                proof
+                    .write(&mut zkproof[..])
+                    .expect("should be able to serialize a proof");
+
+                Ok((zkproof, cv, rk))
+            })
+            .collect()
+    }
+
+    /// Creates Groth16 proofs for several Sapling outputs against a single,
+    /// caller-supplied `ctx`. See [`LocalTxProver::spend_proofs`] for why
+    /// this drives `ctx` sequentially rather than across threads: it is the
+    /// only way for the resulting `bsk`/`cv_sum` bookkeeping to be usable in
+    /// a subsequent `TxProver::binding_sig` call.
+    pub fn output_proofs(
+        &self,
+        ctx: &mut SaplingProvingContext,
+        inputs: Vec<OutputInput>,
+    ) -> Vec<([u8; GROTH_PROOF_SIZE], edwards::Point<Bls12, Unknown>)> {
+        inputs
+            .into_iter()
+            .map(|input| {
+                let (proof, cv) = ctx.output_proof(
+                    input.esk,
+                    input.payment_address,
+                    input.rcm,
+                    input.value,
+                    &self.output_params,
+                    &JUBJUB,
+                );
+
+                let mut zkproof = [0u8; GROTH_PROOF_SIZE];
+                proof
+                    .write(&mut zkproof[..])
+                    .expect("should be able to serialize a proof");
+
+                (zkproof, cv)
+            })
+            .collect()
+    }
+}
+
 impl TxProver for LocalTxProver {
     type SaplingProvingContext = SaplingProvingContext;
 